@@ -0,0 +1,9 @@
+//! `cargo run --bin gen_stubs` prints the `rusty.pyi` stub, rendered from the
+//! registered `Config` field metadata, to stdout; redirect it into
+//! `rusty.pyi` after changing `stub_gen::CONFIG_FIELDS`.
+
+use rusty::stub_gen::{render_config_stub, CONFIG_FIELDS};
+
+fn main() {
+    print!("{}", render_config_stub(CONFIG_FIELDS));
+}
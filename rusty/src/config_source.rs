@@ -0,0 +1,230 @@
+//! Layered resolution of config values, in the spirit of how cargo-config2
+//! resolves layered Cargo config: process environment first, then a user
+//! config file, then a project-local file discovered by walking up from the
+//! current directory, then the hardcoded default. Each resolved value
+//! records where it came from so callers can report provenance.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sentinel filename searched for when walking up from the current directory.
+const PROJECT_CONFIG_FILENAME: &str = ".rusty.toml";
+
+/// Separator joined `EnvType::List`-shaped TOML arrays are stringified with,
+/// matching `DEFAULT_LIST_SEPARATOR` in `lib.rs`.
+const ARRAY_JOIN_SEPARATOR: &str = ",";
+
+/// Where a resolved value came from.
+#[derive(Clone, Debug)]
+pub enum Definition {
+    Environment,
+    File { path: PathBuf, line: usize },
+    Default,
+}
+
+/// A resolved config value plus the source it was read from.
+#[derive(Clone, Debug)]
+pub struct ResolvedValue {
+    pub value: Option<String>,
+    pub origin: Definition,
+}
+
+/// Resolves `env_name` through environment -> user file -> project file ->
+/// `default_value`, returning the first hit. `toml_key` is the key looked up
+/// in both TOML files, which may differ from `env_name` (e.g. `agent_type`
+/// vs. `CODEGEN_AGENT`). Fails with a descriptive message rather than
+/// silently skipping a file whose TOML is malformed, or whose value for
+/// `toml_key` can't be represented as a string.
+pub fn resolve(env_name: &str, toml_key: &str, default_value: Option<&str>) -> Result<ResolvedValue, String> {
+    if let Ok(val) = env::var(env_name) {
+        return Ok(ResolvedValue {
+            value: Some(val),
+            origin: Definition::Environment,
+        });
+    }
+
+    if let Some(home) = home_dir() {
+        let user_config = home.join(".config").join("rusty").join("config.toml");
+        if let Some(found) = lookup_in_file(&user_config, toml_key)? {
+            return Ok(found);
+        }
+    }
+
+    if let Ok(cwd) = env::current_dir() {
+        if let Some(found) = lookup_upward(&cwd, toml_key)? {
+            return Ok(found);
+        }
+    }
+
+    Ok(ResolvedValue {
+        value: default_value.map(|v| v.to_string()),
+        origin: Definition::Default,
+    })
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Walks from `start` up through its ancestors looking for
+/// `PROJECT_CONFIG_FILENAME`, returning the first match containing `toml_key`.
+fn lookup_upward(start: &Path, toml_key: &str) -> Result<Option<ResolvedValue>, String> {
+    let mut dir = Some(start);
+    while let Some(d) = dir {
+        let candidate = d.join(PROJECT_CONFIG_FILENAME);
+        if candidate.is_file() {
+            if let Some(found) = lookup_in_file(&candidate, toml_key)? {
+                return Ok(Some(found));
+            }
+        }
+        dir = d.parent();
+    }
+    Ok(None)
+}
+
+fn lookup_in_file(path: &Path, toml_key: &str) -> Result<Option<ResolvedValue>, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return Ok(None),
+    };
+    let parsed: toml::Value = contents
+        .parse()
+        .map_err(|e| format!("{}: invalid TOML: {e}", path.display()))?;
+    let Some(raw_value) = parsed.get(toml_key) else {
+        return Ok(None);
+    };
+    let value = stringify_toml_value(raw_value)
+        .map_err(|reason| format!("{}: key {toml_key:?} {reason}", path.display()))?;
+    Ok(Some(ResolvedValue {
+        value: Some(value),
+        origin: Definition::File {
+            path: path.to_path_buf(),
+            line: line_of_key(&contents, toml_key),
+        },
+    }))
+}
+
+/// Renders a TOML scalar (or an array of strings) as the string a
+/// `DynamicEnvVar` would coerce, the same way it would coerce a raw
+/// environment variable. Values that can't be represented this way (nested
+/// tables, datetimes, arrays with non-string elements) are a hard error
+/// rather than being treated as though the key were absent.
+fn stringify_toml_value(value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        toml::Value::Float(f) => Ok(f.to_string()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                toml::Value::String(s) => Ok(s.clone()),
+                other => Err(format!("has a non-string array element: {other}")),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|parts| parts.join(ARRAY_JOIN_SEPARATOR)),
+        other => Err(format!("is a {} value, which can't be read as a string", other.type_str())),
+    }
+}
+
+/// Finds the 1-indexed line a top-level `key = ...` entry appears on, for
+/// provenance reporting. Scans every line, so a matching `key = ...` inside a
+/// table is found too. Falls back to `0` if no line starts with the bare key
+/// (e.g. the key is written quoted).
+fn line_of_key(contents: &str, key: &str) -> usize {
+    for (idx, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix(key) {
+            if rest.trim_start().starts_with('=') {
+                return idx + 1;
+            }
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn unique_temp_dir(label: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rusty-config-source-test-{label}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn line_of_key_finds_top_level_entry() {
+        let contents = "foo = 1\nbar = 2\n";
+        assert_eq!(line_of_key(contents, "bar"), 2);
+    }
+
+    #[test]
+    fn line_of_key_returns_zero_when_missing() {
+        let contents = "foo = 1\n";
+        assert_eq!(line_of_key(contents, "bar"), 0);
+    }
+
+    #[test]
+    fn lookup_upward_finds_sentinel_in_ancestor() {
+        let root = unique_temp_dir("upward-found");
+        fs::write(root.join(PROJECT_CONFIG_FILENAME), "agent_type = \"custom_agent\"\n").unwrap();
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+
+        let found = lookup_upward(&nested, "agent_type").unwrap().unwrap();
+        assert_eq!(found.value.as_deref(), Some("custom_agent"));
+        assert!(matches!(found.origin, Definition::File { line: 1, .. }));
+    }
+
+    #[test]
+    fn lookup_upward_returns_none_without_sentinel() {
+        let root = unique_temp_dir("upward-missing");
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+
+        assert!(lookup_upward(&nested, "agent_type").unwrap().is_none());
+    }
+
+    #[test]
+    fn resolve_prefers_environment_over_default() {
+        let env_name = "RUSTY_TEST_RESOLVE_ENV_PRECEDENCE";
+        env::set_var(env_name, "from_env");
+        let resolved = resolve(env_name, "resolve_env_precedence", Some("fallback")).unwrap();
+        env::remove_var(env_name);
+
+        assert_eq!(resolved.value.as_deref(), Some("from_env"));
+        assert!(matches!(resolved.origin, Definition::Environment));
+    }
+
+    #[test]
+    fn resolve_falls_back_to_default() {
+        let env_name = "RUSTY_TEST_RESOLVE_ENV_MISSING_DOES_NOT_EXIST";
+        env::remove_var(env_name);
+        let resolved = resolve(env_name, "resolve_env_missing", Some("fallback")).unwrap();
+
+        assert_eq!(resolved.value.as_deref(), Some("fallback"));
+        assert!(matches!(resolved.origin, Definition::Default));
+    }
+
+    #[test]
+    fn stringify_toml_value_passes_through_scalars_and_string_arrays() {
+        assert_eq!(stringify_toml_value(&toml::Value::Integer(4)).unwrap(), "4");
+        assert_eq!(stringify_toml_value(&toml::Value::Boolean(true)).unwrap(), "true");
+        let array = toml::Value::Array(vec![toml::Value::String("a".into()), toml::Value::String("b".into())]);
+        assert_eq!(stringify_toml_value(&array).unwrap(), "a,b");
+    }
+
+    #[test]
+    fn stringify_toml_value_rejects_tables_and_non_string_arrays() {
+        let mut table = toml::value::Table::new();
+        table.insert("nested".to_string(), toml::Value::Integer(1));
+        assert!(stringify_toml_value(&toml::Value::Table(table)).is_err());
+
+        let mixed_array = toml::Value::Array(vec![toml::Value::Integer(1)]);
+        assert!(stringify_toml_value(&mixed_array).is_err());
+    }
+}
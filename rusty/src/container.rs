@@ -0,0 +1,247 @@
+//! A lightweight dependency-injection container for `Config`, in the spirit
+//! of dilib: Python registers named providers (optionally singleton, and
+//! optionally depending on other registered keys or `DynamicEnvVar`
+//! descriptors), then resolves them by name.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// A registered factory and how to invoke it.
+struct Provider {
+    factory: Py<PyAny>,
+    singleton: bool,
+    dependencies: Vec<String>,
+    cached: Option<PyObject>,
+}
+
+/// Looks up a dependency name that isn't a registered provider, e.g. a
+/// `DynamicEnvVar`-backed `Config` attribute. Returns `None` if `name` isn't
+/// recognized at all.
+pub type EnvLookup<'a> = dyn Fn(Python, &str) -> Option<PyObject> + 'a;
+
+/// Resolution state for `Config`'s providers. Every call into this type
+/// happens while the GIL is held (PyO3 guarantees that for `#[pymethods]`),
+/// so a `Mutex` at the call site is enough to make the `&mut self` methods
+/// below safe to share across the static container instance. `resolve`
+/// takes care to never hold that `Mutex` while running arbitrary Python
+/// (factory calls), since a factory that re-enters `resolve` on the same
+/// thread would otherwise deadlock on the non-reentrant `Mutex`.
+#[derive(Default)]
+pub struct Container {
+    providers: HashMap<String, Provider>,
+    frozen: bool,
+}
+
+thread_local! {
+    /// Keys currently being resolved on this thread, tracked independently
+    /// of the `Mutex` above so that resolving a key which is already being
+    /// resolved (a cycle, whether declared via `depends_on` or through a
+    /// factory calling `resolve` directly) gets a clear error instead of
+    /// recursing without end. Re-entering `resolve` for any other key works.
+    static RESOLVING: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
+
+impl Container {
+    /// Registers a new provider under `key`. Fails once `resolve()` has been
+    /// called on the container (even a call that failed), since providers
+    /// registered after that point could silently change already-cached
+    /// singletons.
+    pub fn register(
+        &mut self,
+        key: String,
+        factory: Py<PyAny>,
+        singleton: bool,
+        dependencies: Vec<String>,
+    ) -> PyResult<()> {
+        if self.frozen {
+            return Err(PyValueError::new_err(format!(
+                "cannot register {key:?}: container is frozen after its first resolve()"
+            )));
+        }
+        self.providers.insert(
+            key,
+            Provider {
+                factory,
+                singleton,
+                dependencies,
+                cached: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Drops every provider's cached singleton instance, so the next
+    /// `resolve()` reconstructs it (picking up any environment changes its
+    /// dependencies read). Registered providers and the frozen state are
+    /// left untouched.
+    pub fn clear_cache(&mut self) {
+        for provider in self.providers.values_mut() {
+            provider.cached = None;
+        }
+    }
+}
+
+/// Resolves `key` against `container`, recursively resolving (and injecting
+/// by name) any declared dependencies. Dependencies that aren't registered
+/// providers fall back to `env_lookup`, which `Config` uses to expose its
+/// `DynamicEnvVar` attributes (e.g. `builder_token`) to factories.
+///
+/// Only ever holds `container`'s lock for short, non-reentrant critical
+/// sections — never while calling into Python — so a factory calling back
+/// into `Config.resolve` can't deadlock on it. Cyclic or re-entrant
+/// resolution (declared via `depends_on`, or a factory calling `resolve`
+/// directly) is instead caught via a thread-local "currently resolving" set
+/// and reported as a `PyValueError`.
+pub fn resolve(
+    container: &Mutex<Container>,
+    py: Python,
+    key: &str,
+    env_lookup: &EnvLookup,
+) -> PyResult<PyObject> {
+    let entered = RESOLVING.with(|resolving| resolving.borrow_mut().insert(key.to_string()));
+    if !entered {
+        return Err(PyValueError::new_err(format!(
+            "cyclic or re-entrant resolve() detected for {key:?}"
+        )));
+    }
+
+    let result = resolve_uncached(container, py, key, env_lookup);
+
+    RESOLVING.with(|resolving| {
+        resolving.borrow_mut().remove(key);
+    });
+    result
+}
+
+fn resolve_uncached(
+    container: &Mutex<Container>,
+    py: Python,
+    key: &str,
+    env_lookup: &EnvLookup,
+) -> PyResult<PyObject> {
+    let (factory, singleton, dependencies) = {
+        let mut guard = container.lock().unwrap();
+        guard.frozen = true;
+        let provider = guard
+            .providers
+            .get(key)
+            .ok_or_else(|| PyValueError::new_err(format!("no provider registered for {key:?}")))?;
+        if let Some(cached) = &provider.cached {
+            return Ok(cached.clone_ref(py));
+        }
+        (
+            provider.factory.clone_ref(py),
+            provider.singleton,
+            provider.dependencies.clone(),
+        )
+    };
+
+    let kwargs = pyo3::types::PyDict::new(py);
+    for dep in &dependencies {
+        let is_provider = container.lock().unwrap().providers.contains_key(dep);
+        let value = if is_provider {
+            resolve(container, py, dep, env_lookup)?
+        } else if let Some(value) = env_lookup(py, dep) {
+            value
+        } else {
+            return Err(PyValueError::new_err(format!(
+                "provider {key:?} depends on unknown key {dep:?}"
+            )));
+        };
+        kwargs.set_item(dep, value)?;
+    }
+
+    let instance = factory.as_ref(py).call((), Some(kwargs))?.into_py(py);
+
+    if singleton {
+        let mut guard = container.lock().unwrap();
+        if let Some(provider) = guard.providers.get_mut(key) {
+            provider.cached = Some(instance.clone_ref(py));
+        }
+    }
+    Ok(instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_env_lookup(_py: Python, _name: &str) -> Option<PyObject> {
+        None
+    }
+
+    #[test]
+    fn dependency_is_injected_by_name() {
+        Python::with_gil(|py| {
+            let container = Mutex::new(Container::default());
+            let factory = py.eval("lambda dep: dep + 1", None, None).unwrap().into_py(py);
+            let dep_factory = py.eval("lambda: 41", None, None).unwrap().into_py(py);
+            container
+                .lock()
+                .unwrap()
+                .register("dep".to_string(), dep_factory, false, Vec::new())
+                .unwrap();
+            container
+                .lock()
+                .unwrap()
+                .register("thing".to_string(), factory, false, vec!["dep".to_string()])
+                .unwrap();
+
+            let result = resolve(&container, py, "thing", &no_env_lookup).unwrap();
+            assert_eq!(result.extract::<i64>(py).unwrap(), 42);
+        });
+    }
+
+    #[test]
+    fn cyclic_dependency_is_rejected() {
+        Python::with_gil(|py| {
+            let container = Mutex::new(Container::default());
+            let factory_a = py.eval("lambda b: b", None, None).unwrap().into_py(py);
+            let factory_b = py.eval("lambda a: a", None, None).unwrap().into_py(py);
+            container
+                .lock()
+                .unwrap()
+                .register("a".to_string(), factory_a, false, vec!["b".to_string()])
+                .unwrap();
+            container
+                .lock()
+                .unwrap()
+                .register("b".to_string(), factory_b, false, vec!["a".to_string()])
+                .unwrap();
+
+            let err = resolve(&container, py, "a", &no_env_lookup).unwrap_err();
+            assert!(err.to_string().contains("cyclic or re-entrant"));
+        });
+    }
+
+    #[test]
+    fn singleton_provider_is_constructed_once() {
+        Python::with_gil(|py| {
+            let container = Mutex::new(Container::default());
+            let counter = py.eval("[0]", None, None).unwrap().into_py(py);
+            let globals = pyo3::types::PyDict::new(py);
+            globals.set_item("counter", counter.clone_ref(py)).unwrap();
+            let factory = py
+                .eval(
+                    "lambda: (counter.__setitem__(0, counter[0] + 1), counter[0])[1]",
+                    Some(globals),
+                    None,
+                )
+                .unwrap()
+                .into_py(py);
+            container
+                .lock()
+                .unwrap()
+                .register("thing".to_string(), factory, true, Vec::new())
+                .unwrap();
+
+            resolve(&container, py, "thing", &no_env_lookup).unwrap();
+            resolve(&container, py, "thing", &no_env_lookup).unwrap();
+
+            assert_eq!(counter.as_ref(py).get_item(0).unwrap().extract::<i64>().unwrap(), 1);
+        });
+    }
+}
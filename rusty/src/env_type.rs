@@ -0,0 +1,139 @@
+//! Target Python types a `DynamicEnvVar` can coerce its resolved string
+//! value into, borrowing the idea of typed, explicitly-parsed config values
+//! from cargo-config2's value layer.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyString};
+
+/// The Python type a `DynamicEnvVar` coerces its resolved value into.
+#[pyclass(module = "rusty")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvType {
+    Str,
+    Int,
+    Bool,
+    Path,
+    List,
+}
+
+impl EnvType {
+    /// The annotation `stub_gen` should emit for a descriptor of this type.
+    pub fn py_type_name(self) -> &'static str {
+        match self {
+            EnvType::Str => "str",
+            EnvType::Int => "int",
+            EnvType::Bool => "bool",
+            EnvType::Path => "Path",
+            EnvType::List => "list[str]",
+        }
+    }
+
+    /// Coerces `raw` into the matching Python value. Failures raise a
+    /// `ValueError` naming `env_name` and the offending value rather than
+    /// falling back silently.
+    pub fn coerce(
+        self,
+        py: Python,
+        env_name: &str,
+        raw: &str,
+        list_separator: &str,
+    ) -> PyResult<PyObject> {
+        match self {
+            EnvType::Str => Ok(PyString::new(py, raw).into_py(py)),
+            EnvType::Int => raw.trim().parse::<i64>().map(|v| v.into_py(py)).map_err(|_| {
+                PyValueError::new_err(format!("{env_name}: cannot parse {raw:?} as an int"))
+            }),
+            EnvType::Bool => match raw.trim().to_lowercase().as_str() {
+                "1" | "true" | "yes" => Ok(true.into_py(py)),
+                "0" | "false" | "no" => Ok(false.into_py(py)),
+                _ => Err(PyValueError::new_err(format!(
+                    "{env_name}: cannot parse {raw:?} as a bool (expected one of 1/0/true/false/yes/no)"
+                ))),
+            },
+            EnvType::Path => {
+                let pathlib = py.import("pathlib")?;
+                Ok(pathlib.getattr("Path")?.call1((raw,))?.into_py(py))
+            }
+            EnvType::List => {
+                let items: Vec<&str> = if raw.is_empty() {
+                    Vec::new()
+                } else {
+                    raw.split(list_separator).collect()
+                };
+                Ok(PyList::new(py, items).into_py(py))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coerces_int() {
+        Python::with_gil(|py| {
+            let value = EnvType::Int.coerce(py, "MAX_WORKERS", "4", ",").unwrap();
+            assert_eq!(value.extract::<i64>(py).unwrap(), 4);
+        });
+    }
+
+    #[test]
+    fn int_coercion_failure_names_env_var_and_value() {
+        Python::with_gil(|py| {
+            let err = EnvType::Int.coerce(py, "MAX_WORKERS", "nope", ",").unwrap_err();
+            let message = err.to_string();
+            assert!(message.contains("MAX_WORKERS"));
+            assert!(message.contains("nope"));
+        });
+    }
+
+    #[test]
+    fn bool_accepts_common_spellings_case_insensitively() {
+        Python::with_gil(|py| {
+            for raw in ["1", "true", "TRUE", "yes"] {
+                let value = EnvType::Bool.coerce(py, "FLAG", raw, ",").unwrap();
+                assert!(value.extract::<bool>(py).unwrap());
+            }
+            for raw in ["0", "false", "FALSE", "no"] {
+                let value = EnvType::Bool.coerce(py, "FLAG", raw, ",").unwrap();
+                assert!(!value.extract::<bool>(py).unwrap());
+            }
+        });
+    }
+
+    #[test]
+    fn bool_rejects_unrecognized_value() {
+        Python::with_gil(|py| {
+            assert!(EnvType::Bool.coerce(py, "FLAG", "maybe", ",").is_err());
+        });
+    }
+
+    #[test]
+    fn list_splits_on_configured_separator() {
+        Python::with_gil(|py| {
+            let value = EnvType::List.coerce(py, "TAGS", "a|b|c", "|").unwrap();
+            let items: Vec<String> = value.extract(py).unwrap();
+            assert_eq!(items, vec!["a".to_string(), "b".to_string(), "c".to_string()]);
+        });
+    }
+
+    #[test]
+    fn list_of_empty_string_is_empty() {
+        Python::with_gil(|py| {
+            let value = EnvType::List.coerce(py, "TAGS", "", ",").unwrap();
+            let items: Vec<String> = value.extract(py).unwrap();
+            assert!(items.is_empty());
+        });
+    }
+
+    #[test]
+    fn path_returns_pathlib_path() {
+        Python::with_gil(|py| {
+            let value = EnvType::Path.coerce(py, "HOME_DIR", "/tmp", ",").unwrap();
+            let name = value.as_ref(py).get_type().name().unwrap();
+            assert_eq!(name, "PosixPath");
+        });
+    }
+}
@@ -1,21 +1,109 @@
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use std::env;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
+
+pub mod config_source;
+pub mod container;
+pub mod env_type;
+pub mod registry;
+pub mod stub_gen;
+
+use config_source::Definition as SourceDefinition;
+use container::Container;
+use env_type::EnvType;
+use registry::Registry;
+use stub_gen::CONFIG_FIELDS;
+
+/// Default separator `EnvType::List` splits on when none is given.
+const DEFAULT_LIST_SEPARATOR: &str = ",";
 
 /// Environment variable descriptor that mimics Python's property behavior
 #[pyclass]
-struct DynamicEnvVar {
+pub(crate) struct DynamicEnvVar {
     env_name: String,
+    /// Key looked up in layered TOML config files, which may differ from
+    /// `env_name` (e.g. `agent_type` vs. `CODEGEN_AGENT`).
+    config_key: String,
     default_value: Option<String>,
+    value_type: EnvType,
+    list_separator: String,
+    /// Memoized resolved value, cleared by `Config.reload()` so environment
+    /// or config-file changes are picked up without restarting.
+    cached: Mutex<Option<Option<String>>>,
+}
+
+impl DynamicEnvVar {
+    /// Builds a descriptor whose TOML lookup key differs from its env var
+    /// name, e.g. `agent_type` in config files vs. `CODEGEN_AGENT` in the
+    /// environment, and which coerces its resolved value into `value_type`.
+    fn with_config_key(
+        env_name: String,
+        config_key: String,
+        default_value: Option<String>,
+        value_type: EnvType,
+    ) -> Self {
+        Self {
+            env_name,
+            config_key,
+            default_value,
+            value_type,
+            list_separator: DEFAULT_LIST_SEPARATOR.to_string(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Drops the memoized resolved value so the next read re-resolves it.
+    fn invalidate_cache(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+
+    fn env_name(&self) -> &str {
+        &self.env_name
+    }
+
+    fn config_key(&self) -> &str {
+        &self.config_key
+    }
+
+    fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
 }
 
 #[pymethods]
 impl DynamicEnvVar {
     #[new]
     fn new(env_name: String, default_value: Option<String>) -> Self {
+        let config_key = env_name.to_lowercase();
+        Self {
+            env_name,
+            config_key,
+            default_value,
+            value_type: EnvType::Str,
+            list_separator: DEFAULT_LIST_SEPARATOR.to_string(),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Builds a descriptor that coerces its resolved value into `value_type`
+    /// instead of always returning a raw string, e.g.
+    /// `DynamicEnvVar.new_typed("MAX_WORKERS", "4", EnvType.Int)`.
+    #[staticmethod]
+    #[pyo3(signature = (env_name, default_value, value_type, list_separator=DEFAULT_LIST_SEPARATOR.to_string()))]
+    fn new_typed(
+        env_name: String,
+        default_value: Option<String>,
+        value_type: EnvType,
+        list_separator: String,
+    ) -> Self {
+        let config_key = env_name.to_lowercase();
         Self {
             env_name,
+            config_key,
             default_value,
+            value_type,
+            list_separator,
+            cached: Mutex::new(None),
         }
     }
 
@@ -37,19 +125,82 @@ impl DynamicEnvVar {
             }
         }
     }
-    
-    /// Helper method to get environment variable value
+
+    /// Helper method to get environment variable value, resolved through the
+    /// layered environment -> user file -> project file -> default chain and
+    /// coerced into `value_type`. The resolved raw value is memoized until
+    /// `Config.reload()` clears it.
     fn get_env_value(&self, py: Python) -> PyResult<PyObject> {
-        match env::var(&self.env_name) {
-            Ok(val) => Ok(val.into_py(py)),
-            Err(_) => match &self.default_value {
-                Some(val) => Ok(val.clone().into_py(py)),
-                None => Ok(py.None()),
+        let mut cache = self.cached.lock().unwrap();
+        if cache.is_none() {
+            let resolved = config_source::resolve(
+                &self.env_name,
+                &self.config_key,
+                self.default_value.as_deref(),
+            )
+            .map_err(PyValueError::new_err)?;
+            *cache = Some(resolved.value);
+        }
+        match cache.clone().unwrap() {
+            Some(raw) => self
+                .value_type
+                .coerce(py, &self.env_name, &raw, &self.list_separator),
+            None => Ok(py.None()),
+        }
+    }
+}
+
+/// Where a resolved config value came from, exposed to Python via
+/// `Config.describe(...)`.
+#[pyclass(module = "rusty")]
+#[derive(Clone)]
+pub struct Definition {
+    /// `"environment"`, `"file"`, or `"default"`.
+    #[pyo3(get)]
+    kind: String,
+    /// Path of the config file the value was read from, if any.
+    #[pyo3(get)]
+    path: Option<String>,
+    /// 1-indexed line the value was found on, if known.
+    #[pyo3(get)]
+    line: Option<usize>,
+}
+
+impl From<SourceDefinition> for Definition {
+    fn from(origin: SourceDefinition) -> Self {
+        match origin {
+            SourceDefinition::Environment => Definition {
+                kind: "environment".to_string(),
+                path: None,
+                line: None,
+            },
+            SourceDefinition::File { path, line } => Definition {
+                kind: "file".to_string(),
+                path: Some(path.display().to_string()),
+                // `config_source` reports an unknown line as 0.
+                line: (line > 0).then_some(line),
+            },
+            SourceDefinition::Default => Definition {
+                kind: "default".to_string(),
+                path: None,
+                line: None,
             },
         }
     }
 }
 
+/// The key, resolved value, and provenance returned by `Config.describe(...)`.
+#[pyclass(module = "rusty")]
+#[derive(Clone)]
+pub struct Described {
+    #[pyo3(get)]
+    key: String,
+    #[pyo3(get)]
+    value: Option<String>,
+    #[pyo3(get)]
+    definition: Definition,
+}
+
 /// Configuration singleton that provides access to environment variables
 #[pyclass(module = "rusty")]
 #[derive(Clone)]
@@ -58,6 +209,36 @@ pub struct Config {
 }
 
 static CONFIG_INSTANCE: OnceLock<Py<Config>> = OnceLock::new();
+static CONTAINER: OnceLock<Mutex<Container>> = OnceLock::new();
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn container() -> &'static Mutex<Container> {
+    CONTAINER.get_or_init(|| Mutex::new(Container::default()))
+}
+
+fn registry() -> &'static Mutex<Registry> {
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+/// Attaches `descriptor` to `Config` under `key` and records it in the
+/// registry, so statically declared fields (via `CONFIG_FIELDS`) and
+/// fields added later with `Config.register_env` are bookkept identically.
+fn register_descriptor(py: Python, key: &str, descriptor: Py<DynamicEnvVar>) -> PyResult<()> {
+    py.get_type::<Config>()
+        .setattr(key, descriptor.clone_ref(py))?;
+    registry().lock().unwrap().insert(key.to_string(), descriptor);
+    Ok(())
+}
+
+/// Resolves a dependency name against `Config`'s `DynamicEnvVar` attributes,
+/// so DI providers can declare a dependency on e.g. `builder_token` (or a
+/// key added later via `Config.register_env`) without it being a registered
+/// provider itself. Consults `registry()` rather than the static
+/// `CONFIG_FIELDS`, since that's what actually backs every live attribute.
+fn lookup_env_field(py: Python, name: &str) -> Option<PyObject> {
+    registry().lock().unwrap().get(py, name)?;
+    py.get_type::<Config>().getattr(name).ok().map(|v| v.into_py(py))
+}
 
 #[pymethods]
 impl Config {
@@ -65,7 +246,7 @@ impl Config {
     fn py_new() -> Self {
         Config {}
     }
-    
+
     /// Get the singleton instance
     #[staticmethod]
     fn instance(py: Python) -> PyResult<Py<Config>> {
@@ -79,30 +260,109 @@ impl Config {
             }
         }
     }
+
+    /// Reports where a config value would be resolved from: the environment,
+    /// a config file (with path and line), or the hardcoded default. Looks
+    /// up `key` in the registry, so it covers both statically declared
+    /// fields and ones added at runtime via `Config.register_env`.
+    fn describe(&self, py: Python, key: String) -> PyResult<Described> {
+        let descriptor = registry()
+            .lock()
+            .unwrap()
+            .get(py, &key)
+            .ok_or_else(|| PyValueError::new_err(format!("unknown config key: {key}")))?;
+        let descriptor = descriptor.borrow(py);
+
+        let resolved = config_source::resolve(
+            descriptor.env_name(),
+            descriptor.config_key(),
+            descriptor.default_value(),
+        )
+        .map_err(PyValueError::new_err)?;
+        Ok(Described {
+            key,
+            value: resolved.value,
+            definition: resolved.origin.into(),
+        })
+    }
+
+    /// Registers a named provider. `depends_on` lists other provider keys
+    /// (or `DynamicEnvVar` attributes like `builder_token`) to resolve and
+    /// pass to `factory` by keyword when this provider is resolved.
+    #[pyo3(signature = (key, factory, singleton=false, depends_on=Vec::new()))]
+    fn register(
+        &self,
+        key: String,
+        factory: Py<PyAny>,
+        singleton: bool,
+        depends_on: Vec<String>,
+    ) -> PyResult<()> {
+        container()
+            .lock()
+            .unwrap()
+            .register(key, factory, singleton, depends_on)
+    }
+
+    /// Resolves a previously registered provider, constructing it (and its
+    /// dependencies) if needed. Freezes the container against further
+    /// `register` calls.
+    fn resolve(&self, py: Python, key: String) -> PyResult<PyObject> {
+        container::resolve(container(), py, &key, &lookup_env_field)
+    }
+
+    /// Creates and attaches a new `DynamicEnvVar`-backed attribute on
+    /// `Config` at runtime, e.g. `Config.register_env("FEATURE_FLAG")`. No
+    /// recompilation needed to add env-backed settings.
+    #[staticmethod]
+    #[pyo3(signature = (key, default=None))]
+    fn register_env(py: Python, key: String, default: Option<String>) -> PyResult<()> {
+        if registry().lock().unwrap().contains(&key) {
+            return Err(PyValueError::new_err(format!(
+                "config key {key:?} is already registered"
+            )));
+        }
+        let descriptor = Py::new(py, DynamicEnvVar::new(key.clone(), default))?;
+        register_descriptor(py, &key, descriptor)
+    }
+
+    /// Clears every descriptor's memoized value and every DI provider's
+    /// cached singleton, so subsequent reads pick up changes to the
+    /// environment or layered config files without restarting the
+    /// interpreter.
+    fn reload(&self, py: Python) {
+        registry().lock().unwrap().clear_all_caches(py);
+        container().lock().unwrap().clear_cache();
+    }
 }
 
 #[pymodule]
 fn rusty(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<Config>()?;
     m.add_class::<DynamicEnvVar>()?;
+    m.add_class::<Definition>()?;
+    m.add_class::<Described>()?;
+    m.add_class::<EnvType>()?;
+
+    // Build the descriptor objects from the shared field metadata, so the
+    // stub generator and the runtime descriptors can't drift apart. Routed
+    // through `register_descriptor` so these behave identically to fields
+    // added later via `Config.register_env`.
+    for field in CONFIG_FIELDS {
+        let descriptor = Py::new(
+            py,
+            DynamicEnvVar::with_config_key(
+                field.env_name.to_string(),
+                field.attr_name.to_string(),
+                field.default_value.map(|v| v.to_string()),
+                field.value_type,
+            ),
+        )?;
+        register_descriptor(py, field.attr_name, descriptor)?;
+    }
 
-    // Create the descriptor objects
-    let builder_token = Py::new(py, DynamicEnvVar::new("BUILDER_TOKEN".to_string(), None))?;
-    let agent_type = Py::new(
-        py,
-        DynamicEnvVar::new("CODEGEN_AGENT".to_string(), Some("trpc_agent".to_string())),
-    )?;
-    let snapshot_bucket = Py::new(py, DynamicEnvVar::new("SNAPSHOT_BUCKET".to_string(), None))?;
-
-    // Add descriptors to Config class
-    let config_type = py.get_type::<Config>();
-    config_type.setattr("builder_token", builder_token)?;
-    config_type.setattr("agent_type", agent_type)?;
-    config_type.setattr("snapshot_bucket", snapshot_bucket)?;
-    
     // Create and expose the singleton CONFIG instance
     let config_instance = Config::instance(py)?;
     m.add("CONFIG", config_instance)?;
-    
+
     Ok(())
 }
@@ -0,0 +1,38 @@
+//! Runtime registry of `DynamicEnvVar` descriptors backing `Config`
+//! attributes. Populated from `stub_gen::CONFIG_FIELDS` at module init and
+//! grown by `Config.register_env(...)`, so static and dynamically
+//! registered keys are bookkept identically. `Config.reload()` walks this
+//! registry to drop each descriptor's cached value.
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::DynamicEnvVar;
+
+#[derive(Default)]
+pub(crate) struct Registry {
+    descriptors: HashMap<String, Py<DynamicEnvVar>>,
+}
+
+impl Registry {
+    pub(crate) fn contains(&self, key: &str) -> bool {
+        self.descriptors.contains_key(key)
+    }
+
+    pub(crate) fn insert(&mut self, key: String, descriptor: Py<DynamicEnvVar>) {
+        self.descriptors.insert(key, descriptor);
+    }
+
+    /// Looks up a registered descriptor by key, covering both the fields
+    /// built from `CONFIG_FIELDS` at module init and ones added later via
+    /// `Config.register_env`.
+    pub(crate) fn get(&self, py: Python, key: &str) -> Option<Py<DynamicEnvVar>> {
+        self.descriptors.get(key).map(|d| d.clone_ref(py))
+    }
+
+    pub(crate) fn clear_all_caches(&self, py: Python) {
+        for descriptor in self.descriptors.values() {
+            descriptor.borrow(py).invalidate_cache();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Generates `rusty.pyi` type stubs for the `rusty` module's `Config` API.
+//!
+//! PyO3 attaches the `DynamicEnvVar` descriptors to `Config` at runtime via
+//! `setattr`, so there's no `__text_signature__` or annotation mypy/IDEs can
+//! read. This module reflects over the same field metadata used to build
+//! those descriptors in `rusty()`, plus the rest of `Config`'s hand-written
+//! `#[pymethods]` API (`describe`, `register`, `resolve`, `register_env`,
+//! `reload`) and its supporting classes (`Definition`, `Described`,
+//! `EnvType`), and renders a matching `.pyi`. The `DynamicEnvVar` class the
+//! module also exports is not declared in the stub.
+
+use std::fmt::Write as _;
+
+use crate::env_type::EnvType;
+
+/// Metadata describing one `Config` attribute backed by a `DynamicEnvVar`.
+pub struct ConfigFieldSpec {
+    pub attr_name: &'static str,
+    pub env_name: &'static str,
+    pub default_value: Option<&'static str>,
+    pub doc: Option<&'static str>,
+    pub value_type: EnvType,
+}
+
+/// The attributes registered on `Config` at module init. This is the single
+/// source of truth for both building the descriptors in `rusty()` and
+/// generating stubs for them, so the two can't drift apart.
+pub const CONFIG_FIELDS: &[ConfigFieldSpec] = &[
+    ConfigFieldSpec {
+        attr_name: "builder_token",
+        env_name: "BUILDER_TOKEN",
+        default_value: None,
+        doc: Some("Token used to authenticate build requests."),
+        value_type: EnvType::Str,
+    },
+    ConfigFieldSpec {
+        attr_name: "agent_type",
+        env_name: "CODEGEN_AGENT",
+        default_value: Some("trpc_agent"),
+        doc: Some("Which codegen agent backend to use."),
+        value_type: EnvType::Str,
+    },
+    ConfigFieldSpec {
+        attr_name: "snapshot_bucket",
+        env_name: "SNAPSHOT_BUCKET",
+        default_value: None,
+        doc: Some("Object storage bucket snapshots are written to."),
+        value_type: EnvType::Str,
+    },
+];
+
+/// Renders the `.pyi` contents: `EnvType`, `Definition`, `Described`,
+/// `Config` and the `CONFIG` constant (not `DynamicEnvVar`). Fields are
+/// sorted by attribute name so regenerating the stub produces a stable diff.
+pub fn render_config_stub(fields: &[ConfigFieldSpec]) -> String {
+    let mut sorted: Vec<&ConfigFieldSpec> = fields.iter().collect();
+    sorted.sort_by_key(|f| f.attr_name);
+
+    let mut out = String::new();
+    out.push_str("# Auto-generated by `cargo run --bin gen_stubs`. Do not edit by hand.\n\n");
+    out.push_str("from typing import Callable\n\n");
+
+    out.push_str("class EnvType:\n");
+    out.push_str("    Str: \"EnvType\"\n");
+    out.push_str("    Int: \"EnvType\"\n");
+    out.push_str("    Bool: \"EnvType\"\n");
+    out.push_str("    Path: \"EnvType\"\n");
+    out.push_str("    List: \"EnvType\"\n");
+    out.push('\n');
+
+    out.push_str("class Definition:\n");
+    out.push_str("    kind: str\n");
+    out.push_str("    path: str | None\n");
+    out.push_str("    line: int | None\n");
+    out.push('\n');
+
+    out.push_str("class Described:\n");
+    out.push_str("    key: str\n");
+    out.push_str("    value: str | None\n");
+    out.push_str("    definition: Definition\n");
+    out.push('\n');
+
+    out.push_str("class Config:\n");
+    for field in &sorted {
+        let base_type = field.value_type.py_type_name();
+        let py_type = if field.default_value.is_some() {
+            base_type.to_string()
+        } else {
+            format!("{base_type} | None")
+        };
+        let _ = writeln!(out, "    {}: {}", field.attr_name, py_type);
+        if let Some(doc) = field.doc {
+            let _ = writeln!(out, "    \"\"\"{}\"\"\"", doc);
+        }
+    }
+    out.push('\n');
+    out.push_str("    @staticmethod\n");
+    out.push_str("    def instance() -> Config: ...\n");
+    out.push_str("    def describe(self, key: str) -> Described: ...\n");
+    out.push_str(
+        "    def register(self, key: str, factory: Callable[..., object], singleton: bool = False, depends_on: list[str] = ...) -> None: ...\n",
+    );
+    out.push_str("    def resolve(self, key: str) -> object: ...\n");
+    out.push_str("    @staticmethod\n");
+    out.push_str("    def register_env(key: str, default: str | None = None) -> None: ...\n");
+    out.push_str("    def reload(self) -> None: ...\n");
+    out.push('\n');
+    out.push_str("CONFIG: Config\n");
+    out
+}